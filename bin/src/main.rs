@@ -3,14 +3,22 @@ extern crate clap;
 
 extern crate moco_abm;
 
-use clap::{AppSettings, Arg};
+use clap::{AppSettings, Arg, ArgMatches, SubCommand};
+use moco_abm::binary;
 use moco_abm::model2d::{LinearSegment2D, Model2D};
+use moco_abm::parse::parse_segments;
+use moco_abm::repl::Session;
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
 
 use std::error::Error;
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 use std::path::PathBuf;
 
+const HISTORY_FILE: &str = ".moco_abm_history";
+
 fn main() {
     if let Err(e) = execute() {
         eprintln!("Error: {}", e);
@@ -20,44 +28,184 @@ fn main() {
 fn execute() -> Result<(), Box<dyn Error>> {
     let matches = app_from_crate!()
         .setting(AppSettings::AllowNegativeNumbers)
-        .arg(
-            Arg::with_name("num")
-                .help("number of points to retrieve")
-                .short("n")
-                .takes_value(true)
-                .required(true),
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("solve")
+                .about("compute a fixed number of points in one shot")
+                .arg(
+                    Arg::with_name("num")
+                        .help("number of points to retrieve")
+                        .short("n")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .help("file with piecewise approximation definition (stdin is used if not set)")
+                        .short("f")
+                        .takes_value(true),
+                )
+                .arg(binary_arg())
+                .arg(reference_arg()),
         )
-        .arg(
-            Arg::with_name("file")
-                .help("file with piecewise approximation definition (stdin is used if not set)")
-                .short("f")
-                .takes_value(true),
+        .subcommand(
+            SubCommand::with_name("repl")
+                .about("interactively explore an approximation one point at a time")
+                .arg(
+                    Arg::with_name("file")
+                        .help("file with piecewise approximation definition to load")
+                        .required(true),
+                )
+                .arg(binary_arg())
+                .arg(reference_arg()),
         )
         .get_matches();
 
+    match matches.subcommand() {
+        ("solve", Some(m)) => solve(m),
+        ("repl", Some(m)) => repl(m),
+        _ => unreachable!("clap enforces a subcommand is present"),
+    }
+}
+
+fn binary_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("binary")
+        .help("read the input in the compact binary format instead of text")
+        .short("b")
+        .long("binary")
+}
+
+fn reference_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("reference")
+        .help("reference point as r0,r1 (required for --binary; overrides a text `reference` directive)")
+        .short("r")
+        .takes_value(true)
+}
+
+fn parse_reference(s: &str) -> Result<[f64; 2], Box<dyn Error>> {
+    let mut parts = s.splitn(2, ',');
+    let r0 = parts.next().ok_or("missing r0 in reference point")?.parse::<f64>()?;
+    let r1 = parts
+        .next()
+        .ok_or("missing r1 in reference point (expected `r0,r1`)")?
+        .parse::<f64>()?;
+    Ok([r0, r1])
+}
+
+fn load_input(
+    input: impl Read,
+    matches: &ArgMatches,
+) -> Result<([f64; 2], Vec<LinearSegment2D<f64>>), Box<dyn Error>> {
+    let (embedded_reference, s) = if matches.is_present("binary") {
+        (None, binary::read_segments(input)?)
+    } else {
+        read_segments(input)?
+    };
+
+    let r = match matches.value_of("reference") {
+        Some(s) => parse_reference(s)?,
+        None => embedded_reference
+            .ok_or("missing reference point (pass -r r0,r1 or include a `reference` directive in the input)")?,
+    };
+
+    Ok((r, s))
+}
+
+fn solve(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
     let n = validate_num(parse_num(matches.value_of("num").unwrap())?)?;
     let f = parse_file(matches.value_of("file"))?;
-    let s = match f {
-        Some(f) => read_segments(f),
-        None => read_segments(io::stdin()),
+    let (r, s) = match f {
+        Some(f) => load_input(f, matches),
+        None => load_input(io::stdin(), matches),
     }?;
 
-    let mut m = Model2D::new(s)?;
+    let mut m = Model2D::new(s, r)?;
 
-    println!("index\thv_contribution\thv_current\thv_relative\tpoint");
-    for i in 1..(n + 1) {
-        let (point, hv_contribution, hv_current, hv_relative) =
-            match m.get_next_point() {
-                Some(r) => r,
-                None => break,
-            };
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    writeln!(out, "index\thv_contribution\thv_current\thv_relative\tpoint")?;
+    m.drain_into_indexed(n, &mut out)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+fn repl(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let file = matches.value_of("file").unwrap();
+    let (r, s) = load_input(File::open(file)?, matches)?;
+    let mut session = Session::new(s, r)?;
 
-        println!(
-            "{}\t{}\t{}\t{}\t{},{}",
-            i, hv_contribution, hv_current, hv_relative, point[0], point[1]
-        );
+    let mut editor = DefaultEditor::new()?;
+    let _ = editor.load_history(HISTORY_FILE);
+
+    loop {
+        match editor.readline("moco-abm> ") {
+            Ok(line) => {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                editor.add_history_entry(line)?;
+                if line == "quit" || line == "exit" {
+                    break;
+                }
+                if let Err(e) = run_repl_command(&mut session, line) {
+                    eprintln!("Error: {}", e);
+                }
+            }
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(Box::new(e)),
+        }
     }
 
+    let _ = editor.save_history(HISTORY_FILE);
+    Ok(())
+}
+
+fn run_repl_command(
+    session: &mut Session<f64>,
+    line: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut words = line.split_whitespace();
+    match words.next().unwrap() {
+        "next" => {
+            let k = match words.next() {
+                Some(v) => v.parse::<usize>()?,
+                None => 1,
+            };
+            for (point, hv_contribution, hv_current, hv_relative) in
+                session.next(k)
+            {
+                println!(
+                    "{:.12}\t{:.12}\t{:.12}\t{:.12},{:.12}",
+                    hv_contribution, hv_current, hv_relative, point[0], point[1]
+                );
+            }
+        }
+        "hv" => {
+            let (current, ratio) = session.hv();
+            println!("hv_current\thv_relative\n{:.12}\t{:.12}", current, ratio);
+        }
+        "remaining" => {
+            println!("{}", session.remaining());
+        }
+        "reset" => {
+            session.reset()?;
+        }
+        "load" => {
+            let path = words.next().ok_or("usage: load <file>")?;
+            let (r, s) = read_segments(File::open(path)?)?;
+            let r = r.ok_or("input must carry a `reference r_1 r_2` directive")?;
+            session.load(s, r)?;
+        }
+        cmd => {
+            return Err(format!(
+                "unknown command `{}` (expected next, hv, remaining, reset, load or quit)",
+                cmd
+            )
+            .into())
+        }
+    }
     Ok(())
 }
 
@@ -88,34 +236,11 @@ fn parse_file(s: Option<&str>) -> Result<Option<File>, String> {
 
 fn read_segments(
     mut r: impl Read,
-) -> Result<Vec<LinearSegment2D>, Box<dyn Error>> {
+) -> Result<(Option<[f64; 2]>, Vec<moco_abm::model2d::LinearSegment2D<f64>>), Box<dyn Error>> {
     let mut buffer = String::new();
     r.read_to_string(&mut buffer)?;
 
-    let mut v = Vec::new();
-    let mut iter = buffer.split_whitespace();
-    loop {
-        let start = [
-            match iter.next() {
-                Some(p) => p.parse::<f64>()?,
-                None => break,
-            },
-            iter.next()
-                .ok_or("missing coordinate data")?
-                .parse::<f64>()?,
-        ];
-        let end = [
-            iter.next()
-                .ok_or("missing coordinate data")?
-                .parse::<f64>()?,
-            iter.next()
-                .ok_or("missing coordinate data")?
-                .parse::<f64>()?,
-        ];
-        v.push(LinearSegment2D::new(start, end));
-    }
-
-    Ok(v)
+    Ok(parse_segments(&buffer)?)
 }
 
 fn validate_num(n: usize) -> Result<usize, &'static str> {
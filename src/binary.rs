@@ -0,0 +1,96 @@
+//! Compact binary encoding for segment approximation files.
+//!
+//! A file is a 4-byte magic, a `u64` little-endian segment count, then that
+//! many packed `f64` quadruples `[x1, y1, x2, y2]`, also little-endian. This
+//! is faster to load and exactly round-trippable, unlike the whitespace text
+//! format handled by [`crate::parse`].
+
+use std::io::{Read, Write};
+
+use crate::error::{Error, ErrorKind};
+use crate::model2d::{LinearSegment2D, Scalar};
+
+const MAGIC: &[u8; 4] = b"MABM";
+
+/// Reads segments from the compact binary format.
+pub fn read_segments<T: Scalar>(
+    mut r: impl Read,
+) -> Result<Vec<LinearSegment2D<T>>, Error> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic).map_err(|e| {
+        Error::with_message(
+            ErrorKind::ParseError,
+            format!("failed to read magic bytes: {}", e),
+        )
+    })?;
+    if &magic != MAGIC {
+        return Err(Error::with_message(
+            ErrorKind::ParseError,
+            "not a moco-abm binary segment file".to_string(),
+        ));
+    }
+
+    let mut count_bytes = [0u8; 8];
+    r.read_exact(&mut count_bytes).map_err(|e| {
+        Error::with_message(
+            ErrorKind::ParseError,
+            format!("failed to read segment count: {}", e),
+        )
+    })?;
+    let count = u64::from_le_bytes(count_bytes);
+
+    // `count` comes straight from the file header and is not trusted: a
+    // corrupt or hostile value must not be used to pre-size the buffer, or
+    // it can make `Vec::with_capacity` try to allocate an unreasonable
+    // amount of memory and abort before a single coordinate is read.
+    let mut segments = Vec::new();
+    let mut buf = [0u8; 32];
+    for i in 0..count {
+        r.read_exact(&mut buf).map_err(|e| {
+            Error::with_message(
+                ErrorKind::ParseError,
+                format!("segment {}: failed to read coordinates: {}", i, e),
+            )
+        })?;
+
+        let start = [
+            decode_coordinate(&buf[0..8], i)?,
+            decode_coordinate(&buf[8..16], i)?,
+        ];
+        let end = [
+            decode_coordinate(&buf[16..24], i)?,
+            decode_coordinate(&buf[24..32], i)?,
+        ];
+
+        segments.push(LinearSegment2D::new(start, end).map_err(|m| {
+            Error::with_message(ErrorKind::ParseError, format!("segment {}: {}", i, m))
+        })?);
+    }
+
+    Ok(segments)
+}
+
+fn decode_coordinate<T: Scalar>(bytes: &[u8], segment: u64) -> Result<T, Error> {
+    let value = f64::from_le_bytes(bytes.try_into().unwrap());
+    T::from(value).ok_or_else(|| {
+        Error::with_message(
+            ErrorKind::ParseError,
+            format!("segment {}: coordinate {} out of range for scalar type", segment, value),
+        )
+    })
+}
+
+/// Writes segments in the compact binary format.
+pub fn write_segments<T: Scalar>(
+    segments: &[LinearSegment2D<T>],
+    mut w: impl Write,
+) -> std::io::Result<()> {
+    w.write_all(MAGIC)?;
+    w.write_all(&(segments.len() as u64).to_le_bytes())?;
+    for s in segments {
+        for coordinate in [s.start()[0], s.start()[1], s.end()[0], s.end()[1]] {
+            w.write_all(&coordinate.to_f64().unwrap().to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
@@ -2,6 +2,7 @@
 pub enum ErrorKind {
     EmptyApproximation,
     EmptyRegion,
+    ParseError,
     UnsortedSegment,
     UnsortedSegments,
     WrongDimensions,
@@ -0,0 +1,5 @@
+pub mod binary;
+pub mod error;
+pub mod model2d;
+pub mod parse;
+pub mod repl;
@@ -1,8 +1,17 @@
-use moco_abm::model2d::{generate_segments, LinearSegment2D, Model2D, Scalar};
+use moco_abm::binary;
+use moco_abm::model2d::{generate_segments, Model2D};
+use moco_abm::parse::parse_segments;
+use moco_abm::repl::Session;
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
 
 use std::env;
 use std::error::Error;
-use std::io::{self, Read};
+use std::fs;
+use std::io::{self, Read, Write};
+
+const HISTORY_FILE: &str = ".moco_abm_history";
 
 fn main() {
     if let Err(e) = execute() {
@@ -15,8 +24,10 @@ fn main() {
 fn usage(program: &str) -> String {
     format!(
         "\
-Usage: 
-  {} k m r_1 r_2 ... r_d [n d]
+Usage:
+  {0} k m r_1 r_2 ... r_d [n d | --binary <file>]
+  {0} repl <file>
+  {0} encode n d <file>
 
 Where:
   k      Number of points to return. Must be greater than 0.
@@ -24,7 +35,13 @@ Where:
   r_i    Value of the reference point on the i-th coordinate.
   n,d    Optional arguments to generate 'n' linear segments for the superellipse
          curve approximation of parameter 'd'. If these are not given, we expect
-         to read a list of segments from stdin (see README for format).\
+         to read a list of segments from stdin (see README for format).
+  --binary <file>
+         Read segments from <file> in the compact binary format instead of
+         generating them or reading the text format from stdin.
+  repl   Load <file> and explore it interactively instead of running a fixed batch.
+  encode Generate 'n' segments of parameter 'd' and write them to <file> in the
+         compact binary format, for fast reloading with --binary.\
 ",
         program
     )
@@ -35,10 +52,34 @@ fn execute() -> Result<(), Box<dyn Error>> {
 
     let _ = args.next();
 
-    let k = args
-        .next()
-        .ok_or("missing argument `k`")?
-        .parse::<usize>()?;
+    match args.next() {
+        Some(arg) if arg == "repl" => {
+            let file = args.next().ok_or("missing argument `<file>`")?;
+            repl(&file)
+        }
+        Some(arg) if arg == "encode" => {
+            let n = args.next().ok_or("missing argument `n`")?.parse::<usize>()?;
+            let d = args.next().ok_or("missing argument `d`")?.parse::<f64>()?;
+            let file = args.next().ok_or("missing argument `<file>`")?;
+            encode(n, d, &file)
+        }
+        Some(k) => solve(k, args),
+        None => Err("missing argument `k`".into()),
+    }
+}
+
+fn encode(n: usize, d: f64, file: &str) -> Result<(), Box<dyn Error>> {
+    let segments = generate_segments::<f64>(n, d)?;
+    let f = fs::File::create(file)?;
+    binary::write_segments(&segments, f)?;
+    Ok(())
+}
+
+fn solve(
+    k: String,
+    mut args: impl Iterator<Item = String>,
+) -> Result<(), Box<dyn Error>> {
+    let k = k.parse::<usize>()?;
 
     let m = args
         .next()
@@ -55,68 +96,120 @@ fn execute() -> Result<(), Box<dyn Error>> {
     }
 
     let s = match args.next() {
+        Some(v) if v == "--binary" => {
+            let file = args
+                .next()
+                .ok_or("missing argument `<file>` for --binary")?;
+            binary::read_segments(fs::File::open(file)?)?
+        }
         Some(v) => {
             let n = v.parse::<usize>()?;
             let d =
                 args.next().ok_or("missing argument 'd'")?.parse::<f64>()?;
             generate_segments(n, d)?
         }
-        None => read_segments(io::stdin())?,
+        None => {
+            let mut buffer = String::new();
+            io::stdin().read_to_string(&mut buffer)?;
+            let (_, s) = parse_segments(&buffer)?;
+            s
+        }
     };
 
     let mut m = Model2D::new(s, [r[0], r[1]])?;
-    let points = m.solve(k);
-
-    println!("hv_contribution\thv_current\thv_relative\tpoint");
-    for (point, hv_contribution, hv_current, hv_relative) in points {
-        println!(
-            "{:.12}\t{:.12}\t{:.12}\t{:.12},{:.12}",
-            hv_contribution, hv_current, hv_relative, point[0], point[1]
-        );
-    }
+
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    writeln!(out, "hv_contribution\thv_current\thv_relative\tpoint")?;
+    m.drain_into(k, &mut out)?;
+    out.flush()?;
 
     Ok(())
 }
 
-fn read_segments<T: Scalar>(
-    mut r: impl Read,
-) -> Result<Vec<LinearSegment2D<T>>, Box<dyn Error>> {
-    let mut buffer = String::new();
-    r.read_to_string(&mut buffer)?;
+fn repl(file: &str) -> Result<(), Box<dyn Error>> {
+    let (r, s) = read_file(file)?;
+    let r = r.ok_or("input must carry a `reference r_1 r_2` directive")?;
+    let mut session = Session::new(s, r)?;
+
+    let mut editor = DefaultEditor::new()?;
+    let _ = editor.load_history(HISTORY_FILE);
 
-    let mut v = Vec::new();
-    let mut iter = buffer.split_whitespace();
     loop {
-        let start = [
-            match iter.next() {
-                Some(s) => T::from_str_radix(s, 10)
-                    .ok()
-                    .ok_or("failed to parse coordinate data")?,
-                None => break,
-            },
-            match iter.next() {
-                Some(s) => T::from_str_radix(s, 10)
-                    .ok()
-                    .ok_or("failed to parse coordinate data")?,
-                None => break,
-            },
-        ];
-        let end = [
-            match iter.next() {
-                Some(s) => T::from_str_radix(s, 10)
-                    .ok()
-                    .ok_or("failed to parse coordinate data")?,
-                None => break,
-            },
-            match iter.next() {
-                Some(s) => T::from_str_radix(s, 10)
-                    .ok()
-                    .ok_or("failed to parse coordinate data")?,
-                None => break,
-            },
-        ];
-        v.push(LinearSegment2D::new(start, end)?);
+        match editor.readline("moco-abm> ") {
+            Ok(line) => {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                editor.add_history_entry(line)?;
+                if line == "quit" || line == "exit" {
+                    break;
+                }
+                if let Err(e) = run_repl_command(&mut session, line) {
+                    eprintln!("Error: {}", e);
+                }
+            }
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(Box::new(e)),
+        }
     }
 
-    Ok(v)
+    let _ = editor.save_history(HISTORY_FILE);
+    Ok(())
+}
+
+fn run_repl_command(
+    session: &mut Session<f64>,
+    line: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut words = line.split_whitespace();
+    match words.next().unwrap() {
+        "next" => {
+            let k = match words.next() {
+                Some(v) => v.parse::<usize>()?,
+                None => 1,
+            };
+            for (point, hv_contribution, hv_current, hv_relative) in
+                session.next(k)
+            {
+                println!(
+                    "{:.12}\t{:.12}\t{:.12}\t{:.12},{:.12}",
+                    hv_contribution, hv_current, hv_relative, point[0], point[1]
+                );
+            }
+        }
+        "hv" => {
+            let (current, ratio) = session.hv();
+            println!("hv_current\thv_relative\n{:.12}\t{:.12}", current, ratio);
+        }
+        "remaining" => {
+            println!("{}", session.remaining());
+        }
+        "reset" => {
+            session.reset()?;
+        }
+        "load" => {
+            let path = words.next().ok_or("usage: load <file>")?;
+            let (r, s) = read_file(path)?;
+            let r = r.ok_or("input must carry a `reference r_1 r_2` directive")?;
+            session.load(s, r)?;
+        }
+        cmd => {
+            return Err(format!(
+                "unknown command `{}` (expected next, hv, remaining, reset, load or quit)",
+                cmd
+            )
+            .into())
+        }
+    }
+    Ok(())
+}
+
+fn read_file(
+    path: &str,
+) -> Result<(Option<[f64; 2]>, Vec<moco_abm::model2d::LinearSegment2D<f64>>), Box<dyn Error>>
+{
+    let buffer = fs::read_to_string(path)?;
+    Ok(parse_segments(&buffer)?)
 }
@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 use std::fmt::Debug;
+use std::io::{self, Write};
 
 use num_traits::real::Real;
 
@@ -43,7 +44,7 @@ struct Region2D<T: Scalar> {
     best_point: Point2D<T>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct LinearSegment2D<T: Scalar> {
     start: Point2D<T>,
     end: Point2D<T>,
@@ -99,14 +100,84 @@ impl<T: Scalar> Model2D<T> {
     }
 
     pub fn solve(&mut self, n: usize) -> Vec<(Point2D<T>, T, T, T)> {
-        let mut v = Vec::with_capacity(n);
-        for _ in 0..n {
-            match self.get_next_point() {
-                Some(p) => v.push(p),
-                None => break,
-            }
+        self.points().take(n).collect()
+    }
+
+    /// Returns an iterator that yields points by repeatedly calling
+    /// [`Model2D::get_next_point`], ending once the regions heap empties.
+    pub fn points(&mut self) -> Points<T> {
+        Points { model: self }
+    }
+
+    /// Hypervolume accumulated so far from points already returned.
+    pub fn current_hv(&self) -> T {
+        self.current_hv
+    }
+
+    /// Total hypervolume achievable once every region is exhausted.
+    pub fn max_hv(&self) -> T {
+        self.max_hv
+    }
+
+    /// Number of non-empty regions still left to explore.
+    pub fn remaining(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Pops up to `k` points from the regions heap, writing each one
+    /// directly to `w` as it is produced instead of buffering them in a
+    /// `Vec`, so peak memory stays proportional to the heap size rather
+    /// than to `k`.
+    pub fn drain_into<W: Write>(&mut self, k: usize, w: &mut W) -> io::Result<()>
+    where
+        T: std::fmt::Display,
+    {
+        for (point, hv_contribution, hv_current, hv_relative) in
+            self.points().take(k)
+        {
+            writeln!(
+                w,
+                "{:.12}\t{:.12}\t{:.12}\t{:.12},{:.12}",
+                hv_contribution, hv_current, hv_relative, point[0], point[1]
+            )?;
         }
-        return v;
+        Ok(())
+    }
+
+    /// Like [`Model2D::drain_into`], but prefixes each row with a 1-based
+    /// index column and prints values with their natural `Display`
+    /// precision, matching the bin front-end's original output.
+    pub fn drain_into_indexed<W: Write>(
+        &mut self,
+        k: usize,
+        w: &mut W,
+    ) -> io::Result<()>
+    where
+        T: std::fmt::Display,
+    {
+        for (i, (point, hv_contribution, hv_current, hv_relative)) in
+            self.points().take(k).enumerate()
+        {
+            writeln!(
+                w,
+                "{}\t{}\t{}\t{}\t{},{}",
+                i + 1, hv_contribution, hv_current, hv_relative, point[0], point[1]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the points of a [`Model2D`], produced by [`Model2D::points`].
+pub struct Points<'a, T: Scalar> {
+    model: &'a mut Model2D<T>,
+}
+
+impl<'a, T: Scalar> Iterator for Points<'a, T> {
+    type Item = (Point2D<T>, T, T, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.model.get_next_point()
     }
 }
 
@@ -225,6 +296,14 @@ impl<T: Scalar> LinearSegment2D<T> {
         }
     }
 
+    pub fn start(&self) -> Point2D<T> {
+        self.start
+    }
+
+    pub fn end(&self) -> Point2D<T> {
+        self.end
+    }
+
     fn best_hv(&mut self, r: Point2D<T>) -> Option<(T, Point2D<T>)> {
         /* Calculate line equation */
         let m = (self.end[1] - self.start[1]) / (self.end[0] - self.start[0]);
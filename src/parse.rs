@@ -0,0 +1,114 @@
+//! Text grammar for piecewise-linear approximation files.
+//!
+//! Each line holds either a comment (`#`-prefixed), a segment given as four
+//! floats `x1 y1 x2 y2`, or a leading `reference r0 r1` directive that lets a
+//! file carry its own reference point instead of relying solely on CLI
+//! arguments. Blank lines are ignored.
+
+use nom::bytes::complete::tag;
+use nom::character::complete::space1;
+use nom::combinator::{all_consuming, map_res};
+use nom::number::complete::double;
+use nom::sequence::{preceded, separated_pair, tuple};
+use nom::IResult;
+
+use crate::error::{Error, ErrorKind};
+use crate::model2d::{LinearSegment2D, Point2D, Scalar};
+
+fn float<T: Scalar>(input: &str) -> IResult<&str, T> {
+    map_res(double, |v| T::from(v).ok_or(()))(input)
+}
+
+fn point<T: Scalar>(input: &str) -> IResult<&str, Point2D<T>> {
+    let (input, (x, _, y)) = tuple((float, space1, float))(input)?;
+    Ok((input, [x, y]))
+}
+
+fn reference_line<T: Scalar>(input: &str) -> IResult<&str, Point2D<T>> {
+    preceded(tuple((tag("reference"), space1)), point)(input)
+}
+
+fn segment_line<T: Scalar>(input: &str) -> IResult<&str, (Point2D<T>, Point2D<T>)> {
+    separated_pair(point, space1, point)(input)
+}
+
+/// Parses a segment approximation file, returning the segments it describes
+/// and, if present, the reference point carried by a leading `reference`
+/// directive.
+///
+/// On malformed input, returns an [`Error`] of kind [`ErrorKind::ParseError`]
+/// naming the 1-based line number and the offending token.
+pub fn parse_segments<T: Scalar>(
+    input: &str,
+) -> Result<(Option<Point2D<T>>, Vec<LinearSegment2D<T>>), Error> {
+    let mut reference = None;
+    let mut segments = Vec::new();
+
+    for (i, raw_line) in input.lines().enumerate() {
+        let line_no = i + 1;
+        let line = raw_line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.split_whitespace().next() == Some("reference") {
+            if reference.is_some() || !segments.is_empty() {
+                return Err(Error::with_message(
+                    ErrorKind::ParseError,
+                    format!(
+                        "line {}: `reference` directive must be the first non-comment line",
+                        line_no
+                    ),
+                ));
+            }
+
+            match all_consuming(reference_line::<T>)(line) {
+                Ok((_, r)) => {
+                    reference = Some(r);
+                    continue;
+                }
+                Err(_) => {
+                    return Err(Error::with_message(
+                        ErrorKind::ParseError,
+                        format!(
+                            "line {}: malformed `reference` directive, expected `reference r0 r1`, found `{}`",
+                            line_no, line
+                        ),
+                    ));
+                }
+            }
+        }
+
+        match all_consuming(segment_line::<T>)(line) {
+            Ok((_, (start, end))) => {
+                let segment = LinearSegment2D::new(start, end).map_err(|m| {
+                    Error::with_message(
+                        ErrorKind::ParseError,
+                        format!("line {}: {}", line_no, m),
+                    )
+                })?;
+                segments.push(segment);
+            }
+            Err(_) => {
+                // Report the first token that doesn't parse as a float,
+                // since that's the one that actually broke the grammar --
+                // not just whichever token happens to come first on the
+                // line.
+                let token = line
+                    .split_whitespace()
+                    .find(|t| t.parse::<f64>().is_err())
+                    .unwrap_or(line);
+                return Err(Error::with_message(
+                    ErrorKind::ParseError,
+                    format!(
+                        "line {}: expected `x1 y1 x2 y2`, found `{}`",
+                        line_no, token
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok((reference, segments))
+}
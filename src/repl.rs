@@ -0,0 +1,63 @@
+//! Session state for interactive exploration of a [`Model2D`].
+//!
+//! This holds the pieces a REPL front-end needs to support `next`, `hv`,
+//! `remaining`, `reset` and `load` commands without re-deriving the model
+//! from scratch on every query.
+
+use crate::error::Error;
+use crate::model2d::{LinearSegment2D, Model2D, Point2D, Scalar};
+
+pub struct Session<T: Scalar> {
+    segments: Vec<LinearSegment2D<T>>,
+    reference: Point2D<T>,
+    model: Model2D<T>,
+}
+
+impl<T: Scalar> Session<T> {
+    pub fn new(
+        segments: Vec<LinearSegment2D<T>>,
+        reference: Point2D<T>,
+    ) -> Result<Self, Error> {
+        let model = Model2D::new(segments.clone(), reference)?;
+        Ok(Session {
+            segments,
+            reference,
+            model,
+        })
+    }
+
+    /// Pops the next `k` best points, stopping early if the regions heap
+    /// empties first.
+    pub fn next(&mut self, k: usize) -> Vec<(Point2D<T>, T, T, T)> {
+        self.model.points().take(k).collect()
+    }
+
+    /// Current achieved hypervolume and its ratio to the maximum.
+    pub fn hv(&self) -> (T, T) {
+        (self.model.current_hv(), self.model.current_hv() / self.model.max_hv())
+    }
+
+    /// Number of non-empty regions still left in the model.
+    pub fn remaining(&self) -> usize {
+        self.model.remaining()
+    }
+
+    /// Rebuilds the model from the segments and reference point it was
+    /// created with, discarding any progress made with `next`.
+    pub fn reset(&mut self) -> Result<(), Error> {
+        self.model = Model2D::new(self.segments.clone(), self.reference)?;
+        Ok(())
+    }
+
+    /// Swaps in a new approximation, replacing the current model entirely.
+    pub fn load(
+        &mut self,
+        segments: Vec<LinearSegment2D<T>>,
+        reference: Point2D<T>,
+    ) -> Result<(), Error> {
+        self.model = Model2D::new(segments.clone(), reference)?;
+        self.segments = segments;
+        self.reference = reference;
+        Ok(())
+    }
+}